@@ -0,0 +1,38 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ObfuscationSection {
+    #[serde(default)]
+    pub strings: bool,
+    pub min_string_length: Option<usize>,
+    pub ignore_strings: Option<Vec<String>>,
+    #[serde(default)]
+    pub control_flow: bool,
+    pub skip_files: Option<Vec<String>>,
+    pub skip_attributes: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdentifiersSection {
+    #[serde(default)]
+    pub rename: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ObfuscateConfig {
+    pub obfuscation: ObfuscationSection,
+    pub identifiers: Option<IdentifiersSection>,
+    pub include: Option<Vec<String>>,
+}
+
+impl ObfuscateConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config at {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse config at {}", path.display()))
+    }
+}
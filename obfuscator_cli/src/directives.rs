@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+/// Inclusive 1-indexed line ranges of the source that each obfuscation pass
+/// must leave untouched, as carved out by inline `// rustfuscator:...`
+/// directive comments.
+///
+/// Ranges are keyed by line number, not byte offset: `syn`/`proc-macro2`
+/// spans only expose line/column positions (via `Span::start()`, which
+/// requires the `span-locations` feature), so line number is the only
+/// coordinate a pass can actually test a node's span against.
+#[derive(Debug, Default, Clone)]
+pub struct DisabledRanges {
+    all: Vec<(usize, usize)>,
+    strings: Vec<(usize, usize)>,
+    control_flow: Vec<(usize, usize)>,
+}
+
+impl DisabledRanges {
+    pub fn strings_disabled_at(&self, line: usize) -> bool {
+        contains(&self.all, line) || contains(&self.strings, line)
+    }
+
+    pub fn control_flow_disabled_at(&self, line: usize) -> bool {
+        contains(&self.all, line) || contains(&self.control_flow, line)
+    }
+}
+
+fn contains(ranges: &[(usize, usize)], line: usize) -> bool {
+    ranges.iter().any(|&(start, end)| line >= start && line <= end)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Pass {
+    All,
+    Strings,
+    ControlFlow,
+}
+
+enum Directive {
+    Off,
+    On,
+    SkipStrings,
+    SkipControlFlow,
+}
+
+fn parse_directive(line: &str) -> Option<Directive> {
+    let rest = line.trim().strip_prefix("//")?.trim();
+    match rest.strip_prefix("rustfuscator:")?.trim() {
+        "off" => Some(Directive::Off),
+        "on" => Some(Directive::On),
+        "skip-strings" => Some(Directive::SkipStrings),
+        "skip-control-flow" => Some(Directive::SkipControlFlow),
+        _ => None,
+    }
+}
+
+/// Scan `source` line-by-line for `// rustfuscator:off` / `// rustfuscator:on`
+/// (and the `skip-strings` / `skip-control-flow` variants), grouping consecutive
+/// directive comments into the line ranges each pass must skip.
+///
+/// An `off` with no matching `on` protects to EOF. Duplicate or nested toggles
+/// of the same kind are idempotent rather than an error.
+pub fn scan_directives(source: &str) -> DisabledRanges {
+    let mut open: HashMap<Pass, usize> = HashMap::new();
+    let mut ranges = DisabledRanges::default();
+    let mut last_line = 0usize;
+
+    for (index, line) in source.lines().enumerate() {
+        let line_no = index + 1;
+        last_line = line_no;
+        if let Some(directive) = parse_directive(line) {
+            match directive {
+                Directive::Off => {
+                    open.entry(Pass::All).or_insert(line_no);
+                }
+                Directive::SkipStrings => {
+                    open.entry(Pass::Strings).or_insert(line_no);
+                }
+                Directive::SkipControlFlow => {
+                    open.entry(Pass::ControlFlow).or_insert(line_no);
+                }
+                Directive::On => close_all(&mut open, &mut ranges, line_no),
+            }
+        }
+    }
+
+    close_all(&mut open, &mut ranges, last_line.max(1));
+    ranges
+}
+
+fn close_all(open: &mut HashMap<Pass, usize>, ranges: &mut DisabledRanges, end: usize) {
+    if let Some(start) = open.remove(&Pass::All) {
+        ranges.all.push((start, end));
+    }
+    if let Some(start) = open.remove(&Pass::Strings) {
+        ranges.strings.push((start, end));
+    }
+    if let Some(start) = open.remove(&Pass::ControlFlow) {
+        ranges.control_flow.push((start, end));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn off_on_protects_enclosed_span() {
+        let src = "fn a() {}\n// rustfuscator:off\nfn b() {}\n// rustfuscator:on\nfn c() {}\n";
+        let ranges = scan_directives(src);
+        assert!(ranges.strings_disabled_at(3));
+        assert!(ranges.control_flow_disabled_at(3));
+        assert!(!ranges.strings_disabled_at(5));
+    }
+
+    #[test]
+    fn unterminated_off_protects_to_eof() {
+        let src = "fn a() {}\n// rustfuscator:off\nfn b() {}\n";
+        let ranges = scan_directives(src);
+        let last_line = src.lines().count();
+        assert!(ranges.strings_disabled_at(3));
+        assert!(ranges.strings_disabled_at(last_line));
+    }
+
+    #[test]
+    fn skip_strings_does_not_disable_control_flow() {
+        let src = "// rustfuscator:skip-strings\nfn b() {}\n// rustfuscator:on\n";
+        let ranges = scan_directives(src);
+        assert!(ranges.strings_disabled_at(2));
+        assert!(!ranges.control_flow_disabled_at(2));
+    }
+
+    #[test]
+    fn duplicate_off_is_idempotent() {
+        let src = "// rustfuscator:off\n// rustfuscator:off\nfn b() {}\n// rustfuscator:on\nfn c() {}\n";
+        let ranges = scan_directives(src);
+        assert!(!ranges.strings_disabled_at(5));
+    }
+}
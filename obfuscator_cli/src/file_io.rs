@@ -0,0 +1,21 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// Write `content` to `path`, creating parent directories as needed, and run
+/// `rustfmt` over it immediately when `format` is set.
+pub fn write_transformed(path: &Path, content: &str, format: bool) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+
+    fs::write(path, content)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    if format {
+        let _ = std::process::Command::new("rustfmt").arg(path).output();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+mod config;
+mod directives;
+mod file_io;
+mod processor;
+mod project_mode;
+
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+use config::ObfuscateConfig;
+use project_mode::{process_project, ArchiveOptions, EmitMode};
+
+/// Obfuscate a Rust project's source tree.
+#[derive(Parser, Debug)]
+#[command(name = "rustfuscator", about = "Obfuscate a Rust project")]
+struct Cli {
+    /// Path to the project to obfuscate
+    input: PathBuf,
+
+    /// Path to write the obfuscated project to
+    #[arg(short, long, default_value = "output")]
+    output: PathBuf,
+
+    /// Path to the obfuscation config file
+    #[arg(short, long, default_value = "rustfuscator.toml")]
+    config: PathBuf,
+
+    /// Run `rustfmt` over the obfuscated output
+    #[arg(short, long)]
+    format: bool,
+
+    /// Scan the project without copying or writing anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Scan the input project without writing; exit non-zero if obfuscation would change any file
+    #[arg(long)]
+    check: bool,
+
+    /// How to deliver the transform results
+    #[arg(long, value_enum, default_value_t = EmitArg::Files)]
+    emit: EmitArg,
+
+    /// Number of lines of context to show around each diff hunk
+    #[arg(long)]
+    diff_ctx: Option<usize>,
+
+    /// Print a per-file changed/skip summary while transforming
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Transform files across N worker threads (default: sequential)
+    #[arg(long)]
+    jobs: Option<usize>,
+
+    /// Package the obfuscated output as a .tar.xz archive at this path
+    #[arg(long)]
+    archive: Option<PathBuf>,
+}
+
+/// CLI spelling of [`EmitMode`] (kept separate so `clap::ValueEnum` stays out of the library API).
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum EmitArg {
+    Files,
+    Stdout,
+    Json,
+    Diff,
+}
+
+impl From<EmitArg> for EmitMode {
+    fn from(value: EmitArg) -> Self {
+        match value {
+            EmitArg::Files => EmitMode::Files,
+            EmitArg::Stdout => EmitMode::Stdout,
+            EmitArg::Json => EmitMode::Json,
+            EmitArg::Diff => EmitMode::Diff,
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let config = ObfuscateConfig::load(&cli.config)?;
+    let archive = cli.archive.map(ArchiveOptions::new);
+
+    process_project(
+        &cli.input,
+        &cli.output,
+        cli.format,
+        &config,
+        cli.dry_run,
+        cli.check,
+        cli.emit.into(),
+        cli.diff_ctx,
+        cli.verbose,
+        cli.jobs,
+        archive.as_ref(),
+    )
+}
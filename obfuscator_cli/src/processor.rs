@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use proc_macro2::Span;
+use std::fs;
+use std::path::Path;
+use syn::spanned::Spanned;
+use syn::visit_mut::{self, VisitMut};
+use syn::{parse_quote, Block, Expr, Lit};
+
+use crate::config::ObfuscateConfig;
+use crate::directives::DisabledRanges;
+
+fn line_of(span: Span) -> usize {
+    span.start().line
+}
+
+/// Read, parse and obfuscate one `.rs` file per the enabled passes in `config`.
+///
+/// Returns `(contents, changed, before)`: `contents` is the transformed source
+/// (or the original if nothing changed), `changed` reports whether anything
+/// was rewritten, and `before` carries the pre-transform source when `changed`
+/// is true (used for diffing/reporting upstream).
+pub fn process_file(
+    file_path: &Path,
+    relative: &Path,
+    config: &ObfuscateConfig,
+    rename_identifiers: bool,
+    disabled: &DisabledRanges,
+) -> Result<(String, bool, Option<String>)> {
+    let _ = rename_identifiers; // identifier renaming is not implemented yet
+
+    let source = fs::read_to_string(file_path)
+        .with_context(|| format!("Failed to read {}", file_path.display()))?;
+
+    if is_skipped(relative, &source, config) {
+        return Ok((source, false, None));
+    }
+
+    let Ok(mut file) = syn::parse_file(&source) else {
+        return Ok((source, false, None));
+    };
+
+    let mut changed = false;
+
+    if config.obfuscation.strings {
+        let mut pass = StringObfuscator { disabled, changed: false };
+        pass.visit_file_mut(&mut file);
+        changed |= pass.changed;
+    }
+
+    if config.obfuscation.control_flow {
+        let mut pass = ControlFlowObfuscator { disabled, changed: false };
+        pass.visit_file_mut(&mut file);
+        changed |= pass.changed;
+    }
+
+    if !changed {
+        return Ok((source, false, None));
+    }
+
+    let transformed = prettyplease::unparse(&file);
+    Ok((transformed, true, Some(source)))
+}
+
+fn is_skipped(relative: &Path, source: &str, config: &ObfuscateConfig) -> bool {
+    if let Some(skip_files) = &config.obfuscation.skip_files {
+        let relative_str = relative.to_string_lossy();
+        if skip_files.iter().any(|pattern| relative_str.contains(pattern.as_str())) {
+            return true;
+        }
+    }
+    if let Some(skip_attrs) = &config.obfuscation.skip_attributes {
+        if skip_attrs.iter().any(|attr| source.contains(attr.as_str())) {
+            return true;
+        }
+    }
+    false
+}
+
+struct StringObfuscator<'a> {
+    disabled: &'a DisabledRanges,
+    changed: bool,
+}
+
+impl VisitMut for StringObfuscator<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Expr::Lit(expr_lit) = expr {
+            if let Lit::Str(lit_str) = &expr_lit.lit {
+                if !self.disabled.strings_disabled_at(line_of(lit_str.span())) {
+                    let value = lit_str.value();
+                    *expr = obfuscate_string_literal(&value);
+                    self.changed = true;
+                }
+            }
+        }
+        visit_mut::visit_expr_mut(self, expr);
+    }
+}
+
+fn obfuscate_string_literal(value: &str) -> Expr {
+    const KEY: u8 = 0x5A;
+    let bytes: Vec<u8> = value.bytes().map(|b| b ^ KEY).collect();
+    parse_quote! {
+        ::rust_code_obfuscator::decode_string(#KEY, &[#(#bytes),*])
+    }
+}
+
+struct ControlFlowObfuscator<'a> {
+    disabled: &'a DisabledRanges,
+    changed: bool,
+}
+
+impl VisitMut for ControlFlowObfuscator<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        // Visit children first so the freshly inserted guard `if` is never visited itself.
+        visit_mut::visit_expr_mut(self, expr);
+        match expr {
+            Expr::If(expr_if) if !self.disabled.control_flow_disabled_at(line_of(expr_if.if_token.span())) => {
+                insert_opaque_guard(&mut expr_if.then_branch);
+                self.changed = true;
+            }
+            Expr::While(expr_while)
+                if !self.disabled.control_flow_disabled_at(line_of(expr_while.while_token.span())) =>
+            {
+                insert_opaque_guard(&mut expr_while.body);
+                self.changed = true;
+            }
+            Expr::Loop(expr_loop)
+                if !self.disabled.control_flow_disabled_at(line_of(expr_loop.loop_token.span())) =>
+            {
+                insert_opaque_guard(&mut expr_loop.body);
+                self.changed = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn insert_opaque_guard(block: &mut Block) {
+    let guard: syn::Stmt = parse_quote! { if 1 == 2 { unreachable!() } };
+    block.stmts.insert(0, guard);
+}
@@ -1,38 +1,146 @@
 use anyhow::{bail, Context, Result};
+use rayon::prelude::*;
+use serde::Serialize;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 use toml_edit::{DocumentMut, Item, Table, value};
 use similar::TextDiff;
 
 use crate::config::ObfuscateConfig;
+use crate::directives::scan_directives;
 use crate::file_io::write_transformed;
 use crate::processor::process_file;
 
+/// How the results of a transform run should be delivered to the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmitMode {
+    /// Write transformed files back to disk (the default, pre-existing behavior).
+    #[default]
+    Files,
+    /// Stream each transformed file's source to stdout behind a path banner, without touching disk.
+    Stdout,
+    /// Collect a structured per-file report and print the whole run as one JSON document.
+    Json,
+    /// Print unified diffs for changed files without writing anything to disk.
+    Diff,
+}
+
+#[derive(Debug, Serialize)]
+struct FileReport {
+    path: String,
+    changed: bool,
+    bytes_before: usize,
+    bytes_after: usize,
+    hunks: Vec<String>,
+}
+
+/// Settings for packaging the obfuscated output tree as a single `.tar.xz` artifact.
+#[derive(Debug, Clone)]
+pub struct ArchiveOptions {
+    pub path: PathBuf,
+    /// xz compression preset, 0 (fastest) through 9 (smallest).
+    pub level: u32,
+    /// LZMA2 dictionary size in bytes. Larger windows improve the ratio on
+    /// source trees at the cost of more memory, the way `cargo-dist`/`rustup`
+    /// packages their release tarballs.
+    pub dict_size: u32,
+}
+
+impl ArchiveOptions {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, level: 9, dict_size: 64 * 1024 * 1024 }
+    }
+}
+
 pub fn process_project(
     input: &Path,
     output: &Path,
     format: bool,
     config: &ObfuscateConfig,
     dry_run: bool,
+    check: bool,
+    emit: EmitMode,
     diff_ctx: Option<usize>,
     verbose: bool,
+    jobs: Option<usize>,
+    archive: Option<&ArchiveOptions>,
 ) -> Result<()> {
-    if dry_run {
-        println!("Dry run: scanning project without copying...");
-        transform_rust_files(input, config, /*format=*/false, /*dry_run=*/true, diff_ctx, verbose)?;
+    if check {
+        println!("Check mode: scanning project for drift without writing...");
+        let changed = transform_rust_files(input, config, /*format=*/false, /*dry_run=*/true, diff_ctx, verbose, EmitMode::Files, jobs)?;
+        if changed.is_empty() {
+            println!("0 files would be obfuscated");
+            return Ok(());
+        }
+        println!("{} file{} would be obfuscated", changed.len(), if changed.len() == 1 { "" } else { "s" });
+        bail!("obfuscation check failed: tree is not up to date");
+    }
+
+    if dry_run || emit != EmitMode::Files {
+        if dry_run {
+            println!("Dry run: scanning project without copying...");
+        }
+        transform_rust_files(input, config, /*format=*/false, dry_run, diff_ctx, verbose, emit, jobs)?;
         return Ok(());
     }
 
     copy_full_structure(input, output)?;
-    transform_rust_files(output, config, format, /*dry_run=*/false, diff_ctx, verbose)?;
-    patch_cargo_toml(output)?;
+    let changed = transform_rust_files(output, config, format, /*dry_run=*/false, diff_ctx, verbose, emit, jobs)?;
+    patch_cargo_toml(output, &changed)?;
 
     if format {
         ensure_rustfmt_installed()?;
         format_rust_files(output)?;
     }
 
+    if let Some(opts) = archive {
+        write_archive(output, opts)?;
+    }
+
+    Ok(())
+}
+
+fn write_archive(project_root: &Path, opts: &ArchiveOptions) -> Result<()> {
+    // If the archive is written inside the tree being packaged, it must not try to include itself.
+    let self_relative = opts.path.strip_prefix(project_root).ok().map(Path::to_path_buf);
+
+    let archive_file = fs::File::create(&opts.path)
+        .with_context(|| format!("Failed to create archive at {}", opts.path.display()))?;
+
+    let mut lzma_opts = xz2::stream::LzmaOptions::new_preset(opts.level)
+        .with_context(|| format!("Invalid xz preset level {}", opts.level))?;
+    lzma_opts.dict_size(opts.dict_size);
+    let mut filters = xz2::stream::Filters::new();
+    filters.lzma2(&lzma_opts);
+    let stream = xz2::stream::Stream::new_stream_encoder(&filters, xz2::stream::Check::Crc64)
+        .context("Failed to initialize xz encoder")?;
+
+    let mut tar_builder = tar::Builder::new(xz2::write::XzEncoder::new_stream(archive_file, stream));
+
+    for entry in WalkDir::new(project_root)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || e.file_name() != "target")
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+    {
+        let path = entry.path();
+        let relative = path.strip_prefix(project_root)?;
+        if self_relative.as_deref() == Some(relative) {
+            continue;
+        }
+        tar_builder
+            .append_path_with_name(path, relative)
+            .with_context(|| format!("Failed to add {} to archive", relative.display()))?;
+    }
+
+    tar_builder
+        .into_inner()
+        .context("Failed to finalize tar archive")?
+        .finish()
+        .context("Failed to finalize xz stream")?;
+
+    println!("✓ Wrote archive to {}", opts.path.display());
     Ok(())
 }
 
@@ -48,6 +156,16 @@ fn copy_full_structure(input: &Path, output: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Everything learned about one `.rs` file during a transform pass, buffered so
+/// it can be flushed in deterministic, path-sorted order after parallel work.
+struct FileOutcome {
+    relative: PathBuf,
+    changed: bool,
+    log: String,
+    write: Option<(PathBuf, String)>,
+    report: Option<FileReport>,
+}
+
 fn transform_rust_files(
     project_root: &Path,
     config: &ObfuscateConfig,
@@ -55,73 +173,265 @@ fn transform_rust_files(
     dry_run: bool,
     diff_ctx: Option<usize>,
     verbose: bool,
-) -> Result<()> {
-    for entry in WalkDir::new(project_root)
+    emit: EmitMode,
+    jobs: Option<usize>,
+) -> Result<Vec<PathBuf>> {
+    let files: Vec<PathBuf> = WalkDir::new(project_root)
         .into_iter()
         .filter_map(Result::ok)
         .filter(|e| e.path().extension().map_or(false, |ext| ext == "rs"))
-    {
-        let file_path = entry.path();
-        let relative = file_path.strip_prefix(project_root)?;
-        let (transformed, changed, before_opt) = process_file(file_path, relative, config, false)?;
+        .map(|e| e.path().to_path_buf())
+        .collect();
+
+    let process = |file_path: &PathBuf| -> Result<FileOutcome> {
+        process_one_file(file_path, project_root, config, format, dry_run, diff_ctx, verbose, emit)
+    };
 
-        if verbose {
-            println!("• {} {}", if changed { "[CHANGED]" } else { "[SKIP]" }, relative.display());
+    let mut outcomes: Vec<FileOutcome> = match jobs {
+        Some(n) if n > 1 => {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(n)
+                .build()
+                .context("Failed to build parallel thread pool")?;
+            pool.install(|| files.par_iter().map(process).collect::<Result<Vec<_>>>())?
         }
+        _ => files.iter().map(process).collect::<Result<Vec<_>>>()?,
+    };
+
+    outcomes.sort_by(|a, b| a.relative.cmp(&b.relative));
+
+    let mut changed_files = Vec::new();
+    let mut reports = Vec::new();
+
+    for outcome in outcomes {
+        if !outcome.log.is_empty() {
+            print!("{}", outcome.log);
+        }
+        if let Some((path, transformed)) = outcome.write {
+            write_transformed(&path, &transformed, format)?;
+        }
+        if let Some(report) = outcome.report {
+            reports.push(report);
+        }
+        if outcome.changed {
+            changed_files.push(outcome.relative);
+        }
+    }
+
+    if emit == EmitMode::Json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    }
+
+    Ok(changed_files)
+}
+
+fn process_one_file(
+    file_path: &Path,
+    project_root: &Path,
+    config: &ObfuscateConfig,
+    format: bool,
+    dry_run: bool,
+    diff_ctx: Option<usize>,
+    verbose: bool,
+    emit: EmitMode,
+) -> Result<FileOutcome> {
+    let relative = file_path.strip_prefix(project_root)?.to_path_buf();
+    let source = fs::read_to_string(file_path)?;
+    let disabled = scan_directives(&source);
+    let (transformed, changed, before_opt) = process_file(file_path, &relative, config, false, &disabled)?;
+
+    let mut log = String::new();
+    if verbose && emit != EmitMode::Json {
+        log.push_str(&format!("• {} {}\n", if changed { "[CHANGED]" } else { "[SKIP]" }, relative.display()));
+    }
 
-        if changed {
-            if let Some(ctx) = diff_ctx {
+    let mut write = None;
+    let mut report = None;
+
+    match emit {
+        EmitMode::Json => {
+            let hunks = match (changed, before_opt.as_ref()) {
+                (true, Some(before)) => diff_hunks(before, &transformed, diff_ctx.unwrap_or(3)),
+                _ => Vec::new(),
+            };
+            report = Some(FileReport {
+                path: relative.display().to_string(),
+                changed,
+                bytes_before: before_opt.as_ref().map_or(transformed.len(), |b| b.len()),
+                bytes_after: transformed.len(),
+                hunks,
+            });
+        }
+        EmitMode::Stdout => {
+            log.push_str(&format!("// ==> {}\n{}\n", relative.display(), transformed));
+        }
+        EmitMode::Diff => {
+            if changed {
                 if let Some(before) = before_opt.as_ref() {
-                    let diff = TextDiff::from_lines(before, &transformed);
-                    let old = format!("{} (before)", relative.display());
-                    let new = format!("{} (after)",  relative.display());
-                    println!("{}", diff.unified_diff().context_radius(ctx).header(&old, &new));
+                    log.push_str(&unified_diff_text(before, &transformed, &relative, diff_ctx.unwrap_or(3)));
                 }
             }
-            if !dry_run {
-                println!("Writing {}", file_path.display());
-                write_transformed(&file_path, &transformed, format)?;
+        }
+        EmitMode::Files => {
+            if changed {
+                if let Some(ctx) = diff_ctx {
+                    if let Some(before) = before_opt.as_ref() {
+                        log.push_str(&unified_diff_text(before, &transformed, &relative, ctx));
+                    }
+                }
+                if !dry_run {
+                    log.push_str(&format!("Writing {}\n", file_path.display()));
+                    write = Some((file_path.to_path_buf(), transformed));
+                }
             }
         }
     }
-    Ok(())
+
+    Ok(FileOutcome { relative, changed, log, write, report })
 }
 
-fn patch_cargo_toml(project_root: &Path) -> Result<()> {
-    for entry in WalkDir::new(project_root)
+fn unified_diff_text(before: &str, after: &str, relative: &Path, ctx: usize) -> String {
+    let diff = TextDiff::from_lines(before, after);
+    let old = format!("{} (before)", relative.display());
+    let new = format!("{} (after)", relative.display());
+    format!("{}\n", diff.unified_diff().context_radius(ctx).header(&old, &new))
+}
+
+fn diff_hunks(before: &str, after: &str, ctx: usize) -> Vec<String> {
+    let diff = TextDiff::from_lines(before, after);
+    diff.unified_diff()
+        .context_radius(ctx)
+        .iter_hunks()
+        .map(|hunk| hunk.to_string())
+        .collect()
+}
+
+fn patch_cargo_toml(project_root: &Path, changed_paths: &[PathBuf]) -> Result<()> {
+    let manifests: Vec<PathBuf> = WalkDir::new(project_root)
         .into_iter()
         .filter_map(Result::ok)
         .filter(|e| e.file_name() == "Cargo.toml")
-    {
-        let cargo_path = entry.path();
+        .map(|e| e.path().to_path_buf())
+        .collect();
+
+    // Discover every `[workspace]` root before classifying members below: WalkDir
+    // yields sibling directories in unsorted filesystem order, so a member's
+    // Cargo.toml can otherwise be reached before its workspace root is known.
+    // A manifest can carry both `[package]` and `[workspace]` (a root-package
+    // workspace) and is still a workspace root for this purpose.
+    let mut workspace_dirs: Vec<PathBuf> = Vec::new();
+    for cargo_path in &manifests {
+        let content = fs::read_to_string(cargo_path)?;
+        let doc = content.parse::<DocumentMut>()?;
+        if doc.contains_key("workspace") {
+            if let Some(dir) = cargo_path.parent() {
+                workspace_dirs.push(dir.to_path_buf());
+            }
+        }
+    }
 
+    for cargo_path in &manifests {
         let content = fs::read_to_string(cargo_path)?;
         let mut doc = content.parse::<DocumentMut>()?;
 
-        // Skip virtual manifests
-        if !doc.contains_key("package") {
+        if doc.contains_key("workspace") {
+            insert_workspace_dependencies(&mut doc)?;
+            fs::write(cargo_path, doc.to_string())?;
+            println!("✓ Patched [workspace.dependencies] in {}", cargo_path.display());
+
+            if !doc.contains_key("package") {
+                continue;
+            }
+            // A root-package workspace is also a member of its own workspace:
+            // fall through so its own [dependencies] get wired below too.
+        } else if !doc.contains_key("package") {
             println!("Skipping patch: {} is a virtual manifest", cargo_path.display());
             continue;
         }
 
-        if doc.get("dependencies").is_none() {
-            doc["dependencies"] = Item::Table(Table::new());
+        let manifest_dir = cargo_path.parent().unwrap_or(project_root);
+        let is_workspace_member = workspace_dirs.iter().any(|root| manifest_dir.starts_with(root));
+
+        if is_workspace_member {
+            if !member_has_changes(manifest_dir, project_root, changed_paths) {
+                continue;
+            }
+            insert_workspace_true_dependencies(&mut doc)?;
+            fs::write(cargo_path, doc.to_string())?;
+            println!("✓ Patched workspace-inherited dependencies in {}", cargo_path.display());
+        } else {
+            insert_pinned_dependencies(&mut doc)?;
+            fs::write(cargo_path, doc.to_string())?;
+            println!("✓ Patched dependencies in {}", cargo_path.display());
         }
+    }
 
-        let deps = doc["dependencies"]
-            .as_table_mut()
-            .context("Expected [dependencies] to be a table")?;
+    Ok(())
+}
 
-        // Only insert if not already present
-        if !deps.contains_key("rust_code_obfuscator") {
-            deps.insert("rust_code_obfuscator", value("0.2.10"));
-        }
-        if !deps.contains_key("cryptify") {
-            deps.insert("cryptify", value("3.1.1"));
-        }
+fn member_has_changes(manifest_dir: &Path, project_root: &Path, changed_paths: &[PathBuf]) -> bool {
+    let Ok(relative_dir) = manifest_dir.strip_prefix(project_root) else {
+        return false;
+    };
+    changed_paths.iter().any(|p| p.starts_with(relative_dir))
+}
+
+fn insert_pinned_dependencies(doc: &mut DocumentMut) -> Result<()> {
+    if doc.get("dependencies").is_none() {
+        doc["dependencies"] = Item::Table(Table::new());
+    }
+
+    let deps = doc["dependencies"]
+        .as_table_mut()
+        .context("Expected [dependencies] to be a table")?;
+
+    if !deps.contains_key("rust_code_obfuscator") {
+        deps.insert("rust_code_obfuscator", value("0.2.10"));
+    }
+    if !deps.contains_key("cryptify") {
+        deps.insert("cryptify", value("3.1.1"));
+    }
+
+    Ok(())
+}
 
-        fs::write(cargo_path, doc.to_string())?;
-        println!("✓ Patched dependencies in {}", cargo_path.display());
+fn insert_workspace_dependencies(doc: &mut DocumentMut) -> Result<()> {
+    if doc.get("workspace").and_then(Item::as_table).map_or(true, |t| !t.contains_key("dependencies")) {
+        doc["workspace"]["dependencies"] = Item::Table(Table::new());
+    }
+
+    let deps = doc["workspace"]["dependencies"]
+        .as_table_mut()
+        .context("Expected [workspace.dependencies] to be a table")?;
+
+    if !deps.contains_key("rust_code_obfuscator") {
+        deps.insert("rust_code_obfuscator", value("0.2.10"));
+    }
+    if !deps.contains_key("cryptify") {
+        deps.insert("cryptify", value("3.1.1"));
+    }
+
+    Ok(())
+}
+
+fn insert_workspace_true_dependencies(doc: &mut DocumentMut) -> Result<()> {
+    if doc.get("dependencies").is_none() {
+        doc["dependencies"] = Item::Table(Table::new());
+    }
+
+    let deps = doc["dependencies"]
+        .as_table_mut()
+        .context("Expected [dependencies] to be a table")?;
+
+    if !deps.contains_key("rust_code_obfuscator") {
+        let mut workspace_true = toml_edit::InlineTable::new();
+        workspace_true.insert("workspace", true.into());
+        deps.insert("rust_code_obfuscator", Item::Value(workspace_true.into()));
+    }
+    if !deps.contains_key("cryptify") {
+        let mut workspace_true = toml_edit::InlineTable::new();
+        workspace_true.insert("workspace", true.into());
+        deps.insert("cryptify", Item::Value(workspace_true.into()));
     }
 
     Ok(())
@@ -200,7 +510,7 @@ mod tests{
             identifiers: None, 
             include: None };
 
-        let result = transform_rust_files(&path, &config, false, true, None, false);
+        let result = transform_rust_files(&path, &config, false, true, None, false, EmitMode::Files, None);
         match  result {
             Ok(_) => {},
             Err(_) => panic!("transform_rust_files fails with error"),
@@ -251,7 +561,7 @@ match x {
             identifiers: None, 
             include: None };
 
-        let result = transform_rust_files(&dir.path(), &config, false, false, None, false);
+        let result = transform_rust_files(&dir.path(), &config, false, false, None, false, EmitMode::Files, None);
         match  result {
             Ok(_) => {},
             Err(_) => panic!("transform_rust_files fails with error"),